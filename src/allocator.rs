@@ -0,0 +1,104 @@
+use crate::{portfolio::Position, strategies::TradeSignal};
+
+/// Decides how many units to take on for a fill, so position sizing is a
+/// first-class, testable component instead of a constant buried in the
+/// order engine's fill handlers.
+pub trait OrderAllocator {
+    /// Returns a signed quantity (negative for short) to fill at `price`,
+    /// given available `cash` and any `existing` position in the pair.
+    fn allocate(
+        &self,
+        cash: f32,
+        price: f32,
+        signal: &TradeSignal,
+        existing: Option<&Position>,
+    ) -> i32;
+}
+
+/// Sizes every entry to a fixed fraction of available cash divided by the
+/// fill price, so position size scales with the portfolio.
+pub struct FixedPercentAllocator {
+    /// fraction of available cash to commit per entry, e.g. `0.1` for 10%.
+    pub percent: f32,
+}
+
+impl OrderAllocator for FixedPercentAllocator {
+    fn allocate(
+        &self,
+        cash: f32,
+        price: f32,
+        signal: &TradeSignal,
+        _existing: Option<&Position>,
+    ) -> i32 {
+        if price <= 0.0 || cash <= 0.0 {
+            return 0;
+        }
+
+        let units = ((cash * self.percent) / price).floor() as i32;
+        if units < 1 {
+            return 0;
+        }
+
+        match signal {
+            TradeSignal::Short => -units,
+            _ => units,
+        }
+    }
+}
+
+/// Preserves the engine's original behavior: every entry is a single unit.
+pub struct FixedUnitAllocator;
+
+impl OrderAllocator for FixedUnitAllocator {
+    fn allocate(
+        &self,
+        _cash: f32,
+        _price: f32,
+        signal: &TradeSignal,
+        _existing: Option<&Position>,
+    ) -> i32 {
+        match signal {
+            TradeSignal::Short => -1,
+            _ => 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_percent_sizes_proportionally_to_cash() {
+        let allocator = FixedPercentAllocator { percent: 0.1 };
+        // 10% of 10_000 at a price of 10 is 100 units.
+        assert_eq!(allocator.allocate(10_000.0, 10.0, &TradeSignal::Long, None), 100);
+    }
+
+    #[test]
+    fn fixed_percent_shorts_are_negative() {
+        let allocator = FixedPercentAllocator { percent: 0.1 };
+        assert_eq!(allocator.allocate(10_000.0, 10.0, &TradeSignal::Short, None), -100);
+    }
+
+    #[test]
+    fn fixed_percent_refuses_a_trade_it_cant_afford() {
+        let allocator = FixedPercentAllocator { percent: 0.1 };
+        // 10% of 5 is 0.5, which can't buy a single unit at price 10.
+        assert_eq!(allocator.allocate(5.0, 10.0, &TradeSignal::Long, None), 0);
+    }
+
+    #[test]
+    fn fixed_percent_refuses_when_out_of_cash() {
+        let allocator = FixedPercentAllocator { percent: 0.1 };
+        assert_eq!(allocator.allocate(0.0, 10.0, &TradeSignal::Long, None), 0);
+        assert_eq!(allocator.allocate(-50.0, 10.0, &TradeSignal::Long, None), 0);
+    }
+
+    #[test]
+    fn fixed_unit_always_takes_a_single_unit() {
+        let allocator = FixedUnitAllocator;
+        assert_eq!(allocator.allocate(0.0, 10.0, &TradeSignal::Long, None), 1);
+        assert_eq!(allocator.allocate(0.0, 10.0, &TradeSignal::Short, None), -1);
+    }
+}
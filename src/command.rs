@@ -0,0 +1,183 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::{
+    event::Event,
+    order_engine::OrderEvent,
+    portfolio::Portfolio,
+    Command, MarketPair,
+};
+
+/// Central command-in / event-out control surface for the engine. This
+/// is the one place (not per-trader) that acts on `Command`s sent down
+/// `cmd_rx`, mutating the shared portfolio and reporting back over
+/// `event_sender`.
+///
+/// `cmd_rx` is a single multi-consumer channel, so it must only ever
+/// have one reader — this one. Commands that a `Trader` itself needs to
+/// observe (currently just `ForceExit`) are fanned out explicitly over
+/// `trader_senders`, each trader's own dedicated command channel.
+pub struct CommandEngine {
+    pub portfolio: Arc<Mutex<Portfolio>>,
+    pub order_sender: flume::Sender<OrderEvent>,
+    pub event_sender: flume::Sender<Event>,
+    pub trader_senders: Vec<flume::Sender<Command>>,
+}
+
+impl CommandEngine {
+    pub fn start(self, cmd_rx: flume::Receiver<Command>) {
+        std::thread::spawn(move || {
+            tracing::info!("Starting command handler");
+
+            while let Ok(cmd) = cmd_rx.recv() {
+                match cmd {
+                    Command::ForceExit => {
+                        tracing::trace!("ForceExit observed by command engine, broadcasting to traders");
+                        for trader_sender in &self.trader_senders {
+                            let _ = trader_sender
+                                .send(Command::ForceExit)
+                                .inspect_err(|why| tracing::error!("{why}"));
+                        }
+                    }
+
+                    Command::CloseAllPositions => {
+                        let pairs: Vec<MarketPair> =
+                            self.portfolio.lock().positions.keys().cloned().collect();
+
+                        for pair in pairs {
+                            let _ = self
+                                .order_sender
+                                .send(OrderEvent::Close(pair.clone(), Arc::clone(&self.portfolio)))
+                                .inspect_err(|why| tracing::error!("{why}"));
+                            let _ = self.event_sender.send(Event::PositionClosed(pair));
+                        }
+                    }
+
+                    Command::AddPortfolioPosition(pair, position) => {
+                        self.portfolio
+                            .lock()
+                            .positions
+                            .insert(pair.clone(), position.clone());
+                        let _ = self.event_sender.send(Event::PositionOpened(pair, position));
+                    }
+
+                    Command::PortfolioStatus => {
+                        let ptf = self.portfolio.lock();
+                        let cash = ptf.cash;
+                        let equity = ptf.equity + ptf.unrealized_pnl();
+                        drop(ptf);
+                        let _ = self.event_sender.send(Event::Balance { cash, equity });
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Duration};
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::portfolio::Position;
+
+    const TIMEOUT: Duration = Duration::from_millis(200);
+
+    /// Starts a `CommandEngine` against a fresh in-memory portfolio and
+    /// one fanned-out trader channel, returning the handles a test needs
+    /// to drive and observe it.
+    fn setup() -> (
+        flume::Sender<Command>,
+        flume::Receiver<Event>,
+        flume::Receiver<OrderEvent>,
+        flume::Receiver<Command>,
+        Arc<Mutex<Portfolio>>,
+    ) {
+        let portfolio = Arc::new(Mutex::new(
+            Portfolio::builder()
+                .engine_id(Uuid::new_v4())
+                .markets(Vec::new())
+                .positions(HashMap::new())
+                .build(),
+        ));
+        let (cmd_tx, cmd_rx) = flume::bounded(8);
+        let (order_tx, order_rx) = flume::bounded(8);
+        let (event_tx, event_rx) = flume::bounded(8);
+        let (trader_tx, trader_rx) = flume::bounded(8);
+
+        CommandEngine {
+            portfolio: Arc::clone(&portfolio),
+            order_sender: order_tx,
+            event_sender: event_tx,
+            trader_senders: vec![trader_tx],
+        }
+        .start(cmd_rx);
+
+        (cmd_tx, event_rx, order_rx, trader_rx, portfolio)
+    }
+
+    #[test]
+    fn force_exit_is_fanned_out_to_every_trader_channel() {
+        let (cmd_tx, _event_rx, _order_rx, trader_rx, _portfolio) = setup();
+
+        cmd_tx.send(Command::ForceExit).unwrap();
+
+        assert!(matches!(trader_rx.recv_timeout(TIMEOUT), Ok(Command::ForceExit)));
+    }
+
+    #[test]
+    fn close_all_positions_closes_every_open_position() {
+        let (cmd_tx, event_rx, order_rx, _trader_rx, portfolio) = setup();
+        let pair = MarketPair::new("SUI", "USD");
+        portfolio
+            .lock()
+            .positions
+            .insert(pair.clone(), Position::new(10.0, 5));
+
+        cmd_tx.send(Command::CloseAllPositions).unwrap();
+
+        assert!(matches!(
+            order_rx.recv_timeout(TIMEOUT),
+            Ok(OrderEvent::Close(p, _)) if p == pair
+        ));
+        assert!(matches!(
+            event_rx.recv_timeout(TIMEOUT),
+            Ok(Event::PositionClosed(p)) if p == pair
+        ));
+    }
+
+    #[test]
+    fn add_portfolio_position_inserts_and_emits_an_event() {
+        let (cmd_tx, event_rx, _order_rx, _trader_rx, portfolio) = setup();
+        let pair = MarketPair::new("SOL", "USD");
+        let position = Position::new(20.0, 3);
+
+        cmd_tx
+            .send(Command::AddPortfolioPosition(pair.clone(), position.clone()))
+            .unwrap();
+
+        assert!(matches!(
+            event_rx.recv_timeout(TIMEOUT),
+            Ok(Event::PositionOpened(p, _)) if p == pair
+        ));
+        assert_eq!(portfolio.lock().positions.get(&pair).unwrap().size, 3);
+    }
+
+    #[test]
+    fn portfolio_status_reports_cash_and_equity() {
+        let (cmd_tx, event_rx, _order_rx, _trader_rx, _portfolio) = setup();
+
+        cmd_tx.send(Command::PortfolioStatus).unwrap();
+
+        match event_rx.recv_timeout(TIMEOUT) {
+            Ok(Event::Balance { cash, equity }) => {
+                assert_eq!(cash, 10_000.0);
+                assert_eq!(equity, 0.0);
+            }
+            other => panic!("expected Balance event, got {}", other.is_ok()),
+        }
+    }
+}
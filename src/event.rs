@@ -0,0 +1,13 @@
+use crate::{portfolio::Position, MarketPair};
+
+/// Outbound activity emitted by the engine so its activity can be
+/// consumed as an audit log / event stream, rather than only appearing
+/// in `tracing` output.
+#[derive(Debug, Clone)]
+pub enum Event {
+    PositionOpened(MarketPair, Position),
+    PositionClosed(MarketPair),
+    SignalGenerated(MarketPair),
+    OrderFilled(MarketPair),
+    Balance { cash: f32, equity: f32 },
+}
@@ -0,0 +1,234 @@
+use std::time::Duration;
+
+use time::OffsetDateTime;
+
+use crate::{trader::Candle, Command, MarketEvent};
+
+/// A single step pulled off a [`MarketFeed`]: either a new candle arriving
+/// or a strategy-evaluation tick.
+pub enum FeedEvent {
+    Candle(Candle),
+    Tick,
+}
+
+/// What woke a [`MarketFeed::next`] call up.
+pub enum FeedOutcome {
+    Exit,
+    Command(Command),
+    Feed(FeedEvent),
+    /// Nothing usable was received. For a live feed this happens when the
+    /// exit channel carries `false` or a selected channel is disconnected;
+    /// for a historical feed it means the feed is exhausted — check
+    /// [`MarketFeed::is_exhausted`].
+    Idle,
+}
+
+/// Source of market data and timing for a [`crate::trader::Trader`].
+///
+/// `LiveFeed` blocks on a select across the exit, command and
+/// market-event channels plus its own ticker, so the trader thread wakes
+/// only when there's real work. `HistoricalFeed` replays a fixed slice of
+/// candles and derives its own ticks from them, running flat-out to
+/// exhaustion. Both drive the exact same `Trader` loop, so backtests
+/// exercise the identical decision path as live trading.
+pub trait MarketFeed {
+    fn next(
+        &mut self,
+        exit_recv: &flume::Receiver<bool>,
+        command_recv: &flume::Receiver<Command>,
+    ) -> FeedOutcome;
+
+    /// Whether the feed has no more events left to produce. Live feeds
+    /// never exhaust; historical feeds do once their candles are drained.
+    fn is_exhausted(&self) -> bool {
+        false
+    }
+}
+
+/// Feeds a trader from the live market-event channel plus a ticker
+/// thread, selecting across both of those and the trader's own exit and
+/// command channels so the loop only wakes on real work.
+pub struct LiveFeed {
+    market_event_recv: flume::Receiver<MarketEvent>,
+    ticker: flume::Receiver<()>,
+}
+
+impl LiveFeed {
+    pub fn new(market_event_recv: flume::Receiver<MarketEvent>, tick_rate: Duration) -> LiveFeed {
+        let (ticker_tx, ticker_rx) = flume::bounded(1);
+        std::thread::spawn(move || loop {
+            std::thread::sleep(tick_rate);
+            if ticker_tx.send(()).is_err() {
+                break;
+            }
+        });
+
+        LiveFeed {
+            market_event_recv,
+            ticker: ticker_rx,
+        }
+    }
+}
+
+impl MarketFeed for LiveFeed {
+    fn next(
+        &mut self,
+        exit_recv: &flume::Receiver<bool>,
+        command_recv: &flume::Receiver<Command>,
+    ) -> FeedOutcome {
+        // Drain anything already queued first, so a burst of candles
+        // between ticks is fully folded into market_data before the next
+        // strategy evaluation, rather than racing the ticker in select.
+        if let Ok(MarketEvent::Ohlc(candle)) = self.market_event_recv.try_recv() {
+            return FeedOutcome::Feed(FeedEvent::Candle(candle));
+        }
+
+        flume::Selector::new()
+            .recv(exit_recv, |res| match res {
+                Ok(true) => FeedOutcome::Exit,
+                Ok(false) | Err(_) => FeedOutcome::Idle,
+            })
+            .recv(command_recv, |res| match res {
+                Ok(cmd) => FeedOutcome::Command(cmd),
+                Err(_) => FeedOutcome::Idle,
+            })
+            .recv(&self.market_event_recv, |res| match res {
+                Ok(MarketEvent::Ohlc(candle)) => FeedOutcome::Feed(FeedEvent::Candle(candle)),
+                Err(_) => FeedOutcome::Idle,
+            })
+            .recv(&self.ticker, |res| match res {
+                Ok(()) => FeedOutcome::Feed(FeedEvent::Tick),
+                Err(_) => FeedOutcome::Idle,
+            })
+            .wait()
+    }
+}
+
+/// Replays a fixed set of historical candles, ticking the strategy once
+/// per candle so a backtest produces the same decisions a live trader
+/// would have made on that history. Runs flat-out with no blocking —
+/// exhaustion is the only stop condition.
+pub struct HistoricalFeed {
+    candles: std::vec::IntoIter<Candle>,
+    pending_tick: bool,
+}
+
+impl HistoricalFeed {
+    pub fn new(candles: Vec<Candle>) -> HistoricalFeed {
+        HistoricalFeed {
+            candles: candles.into_iter(),
+            pending_tick: false,
+        }
+    }
+}
+
+impl MarketFeed for HistoricalFeed {
+    fn next(
+        &mut self,
+        exit_recv: &flume::Receiver<bool>,
+        command_recv: &flume::Receiver<Command>,
+    ) -> FeedOutcome {
+        if let Ok(true) = exit_recv.try_recv() {
+            return FeedOutcome::Exit;
+        }
+        if let Ok(cmd) = command_recv.try_recv() {
+            return FeedOutcome::Command(cmd);
+        }
+
+        if self.pending_tick {
+            self.pending_tick = false;
+            return FeedOutcome::Feed(FeedEvent::Tick);
+        }
+
+        match self.candles.next() {
+            Some(candle) => {
+                self.pending_tick = true;
+                FeedOutcome::Feed(FeedEvent::Candle(candle))
+            }
+            None => FeedOutcome::Idle,
+        }
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.candles.len() == 0 && !self.pending_tick
+    }
+}
+
+/// Source of "now" for a trader loop — wall-clock in live trading, the
+/// timestamp of the most recently replayed candle in a backtest.
+pub trait Clock {
+    fn now(&self) -> i64;
+
+    /// Advance "now" to `timestamp`. No-op for a real-time clock; a
+    /// backtest clock uses this to track the timestamp of the most
+    /// recently replayed candle instead of wall-clock time.
+    fn advance_to(&mut self, _timestamp: i64) {}
+}
+
+pub struct WallClock;
+
+impl Clock for WallClock {
+    fn now(&self) -> i64 {
+        OffsetDateTime::now_utc().unix_timestamp()
+    }
+}
+
+#[derive(Default)]
+pub struct BacktestClock {
+    current: i64,
+}
+
+impl Clock for BacktestClock {
+    fn now(&self) -> i64 {
+        self.current
+    }
+
+    fn advance_to(&mut self, timestamp: i64) {
+        self.current = timestamp;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn live_feed_reports_exit_when_exit_channel_fires() {
+        let (market_event_tx, market_event_rx) = flume::bounded(1);
+        let (exit_tx, exit_rx) = flume::bounded(1);
+        let (_cmd_tx, cmd_rx) = flume::bounded(1);
+        let mut feed = LiveFeed::new(market_event_rx, Duration::from_secs(3600));
+
+        exit_tx.send(true).unwrap();
+        assert!(matches!(feed.next(&exit_rx, &cmd_rx), FeedOutcome::Exit));
+
+        drop(market_event_tx);
+    }
+
+    #[test]
+    fn historical_feed_alternates_candle_and_tick_then_exhausts() {
+        let (_exit_tx, exit_rx) = flume::bounded(1);
+        let (_cmd_tx, cmd_rx) = flume::bounded(1);
+        let candle = Candle {
+            open: 1.0,
+            high: 1.0,
+            low: 1.0,
+            close: 1.0,
+            volume: 0,
+            timestamp: 0,
+        };
+        let mut feed = HistoricalFeed::new(vec![candle]);
+
+        assert!(!feed.is_exhausted());
+        assert!(matches!(
+            feed.next(&exit_rx, &cmd_rx),
+            FeedOutcome::Feed(FeedEvent::Candle(_))
+        ));
+        assert!(matches!(
+            feed.next(&exit_rx, &cmd_rx),
+            FeedOutcome::Feed(FeedEvent::Tick)
+        ));
+        assert!(feed.is_exhausted());
+        assert!(matches!(feed.next(&exit_rx, &cmd_rx), FeedOutcome::Idle));
+    }
+}
@@ -1,8 +1,14 @@
 #![allow(unused)]
+mod allocator;
+mod command;
 mod config;
+mod event;
+mod feed;
 mod indicators;
 mod order_engine;
 mod portfolio;
+mod resolution;
+mod storage;
 mod strategies;
 mod trader;
 
@@ -14,18 +20,22 @@ use std::{
 
 use bon::Builder;
 
+use command::CommandEngine;
 use crossbeam::channel::tick;
+use event::Event;
 use order_engine::{OrderEngine, OrderEvent};
 use parking_lot::Mutex;
 use portfolio::{Portfolio, Position};
 use rand::{seq::SliceRandom, Rng};
 use rayon::{ThreadPool, ThreadPoolBuilder};
+use resolution::Resolution;
 use serde::Deserialize;
+use storage::{NullStore, PostgresStore, Store};
 use strategies::{Rsi, SimpleStrat};
 use time::{serde::timestamp, OffsetDateTime};
 use tracing::{info, instrument, Level};
 use tracing_subscriber::util::SubscriberInitExt;
-use trader::{Candle, MarketData, Trader, TradingEngine};
+use trader::{Candle, EngineMode, MarketData, Trader, TradingEngine, TradingEngineOutcome};
 use uuid::Uuid;
 
 enum MarketEvent {
@@ -57,7 +67,7 @@ enum Command {
     ForceExit,
     PortfolioStatus,
     CloseAllPositions,
-    AddPortfolioPosition(Position),
+    AddPortfolioPosition(MarketPair, Position),
 }
 
 fn main() {
@@ -80,9 +90,29 @@ fn main() {
         nq_market.clone(),
     ];
 
+    // `cmd_rx` is consumed solely by the `CommandEngine` below — it's a
+    // multi-consumer queue, so handing clones of it to the `Trader`s too
+    // would mean each command is delivered to exactly one random
+    // subscriber instead of all of them. Each `Trader` instead gets its
+    // own dedicated command channel that the `CommandEngine` fans
+    // `ForceExit` out to explicitly.
     let (cmd_tx, cmd_rx) = flume::bounded::<Command>(128);
+    let (sui_cmd_tx, sui_cmd_rx) = flume::bounded::<Command>(16);
+    let (btc_cmd_tx, btc_cmd_rx) = flume::bounded::<Command>(16);
+    let (nq_cmd_tx, nq_cmd_rx) = flume::bounded::<Command>(16);
+    let (sol_cmd_tx, sol_cmd_rx) = flume::bounded::<Command>(16);
     let (order_tx, order_rx) = flume::bounded::<OrderEvent>(128);
     let (market_event_tx, market_event_rx) = flume::bounded::<MarketEvent>(128);
+    let (event_tx, event_rx) = flume::bounded::<Event>(256);
+
+    // persistence is opt-in: with no DATABASE_URL set, the engine stays
+    // pure in-memory and NullStore is a no-op.
+    let store: Arc<dyn Store + Send + Sync> = match std::env::var("DATABASE_URL") {
+        Ok(conn_str) => Arc::new(
+            PostgresStore::connect(&conn_str).expect("failed to connect to persistence database"),
+        ),
+        Err(_) => Arc::new(NullStore),
+    };
 
     let portfolio = Arc::new(Mutex::new(
         Portfolio::builder()
@@ -96,10 +126,18 @@ fn main() {
         .engine_id(engine_id)
         .market_pair(sui_market)
         .market_data(MarketData::new())
-        .command_recv(cmd_rx.clone())
+        .command_recv(sui_cmd_rx)
         .portfolio(Arc::clone(&portfolio))
         .order_sender(order_tx.clone())
-        .strategy(Box::new(Rsi { period: 14 }))
+        .event_sender(event_tx.clone())
+        .store(Arc::clone(&store))
+        .resolution(Resolution::M1)
+        .strategy(Box::new(Rsi {
+            period: 14,
+            oversold: 30.0,
+            overbought: 70.0,
+            resolution: Resolution::M1,
+        }))
         .tick_rate(Duration::from_secs(5))
         .build();
 
@@ -107,10 +145,18 @@ fn main() {
         .engine_id(engine_id)
         .market_pair(btc_market)
         .market_data(MarketData::new())
-        .command_recv(cmd_rx.clone())
+        .command_recv(btc_cmd_rx)
         .portfolio(Arc::clone(&portfolio))
         .order_sender(order_tx.clone())
-        .strategy(Box::new(Rsi { period: 14 }))
+        .event_sender(event_tx.clone())
+        .store(Arc::clone(&store))
+        .resolution(Resolution::M1)
+        .strategy(Box::new(Rsi {
+            period: 14,
+            oversold: 30.0,
+            overbought: 70.0,
+            resolution: Resolution::M1,
+        }))
         .tick_rate(Duration::from_secs(15))
         .build();
 
@@ -118,9 +164,12 @@ fn main() {
         .engine_id(engine_id)
         .market_pair(nq_market)
         .market_data(MarketData::new())
-        .command_recv(cmd_rx.clone())
+        .command_recv(nq_cmd_rx)
         .portfolio(Arc::clone(&portfolio))
         .order_sender(order_tx.clone())
+        .event_sender(event_tx.clone())
+        .store(Arc::clone(&store))
+        .resolution(Resolution::M1)
         .strategy(Box::new(SimpleStrat {}))
         .tick_rate(Duration::from_secs(30))
         .build();
@@ -129,22 +178,50 @@ fn main() {
         .engine_id(engine_id)
         .market_pair(sol_market)
         .market_data(MarketData::new())
-        .command_recv(cmd_rx.clone())
+        .command_recv(sol_cmd_rx)
         .portfolio(Arc::clone(&portfolio))
         .strategy(Box::new(SimpleStrat {}))
         .order_sender(order_tx.clone())
+        .event_sender(event_tx.clone())
+        .store(Arc::clone(&store))
+        .resolution(Resolution::M1)
         .tick_rate(Duration::from_secs(2))
         .build();
 
     let traders = vec![sui_trader, sol_trader, btc_trader, nq_trader];
 
-    let trading_engine_handle = TradingEngine::builder()
+    let trading_engine_handle = match TradingEngine::builder()
         .engine_id(engine_id)
         .traders(traders)
         .build()
-        .start();
+        .start(EngineMode::Live)
+        .expect("failed to start trading engine")
+    {
+        TradingEngineOutcome::Live(handle) => handle,
+        TradingEngineOutcome::Backtest(_) => unreachable!("main always runs in live mode"),
+    };
+    // Held for the life of the program: dropping it closes every trader's
+    // exit channel, which would busy-spin their `Selector::wait` loops
+    // instead of actually stopping them. See `TradingEngineHandle`.
+    let _engine_stop = trading_engine_handle.stop.clone();
+
+    OrderEngine::with_default_allocator(event_tx.clone(), Arc::clone(&store)).start(order_rx);
+
+    CommandEngine {
+        portfolio: Arc::clone(&portfolio),
+        order_sender: order_tx.clone(),
+        event_sender: event_tx.clone(),
+        trader_senders: vec![sui_cmd_tx, sol_cmd_tx, btc_cmd_tx, nq_cmd_tx],
+    }
+    .start(cmd_rx);
 
-    OrderEngine::default().start(order_rx);
+    // drains the event stream so engine activity is observable as an
+    // audit log, independent of the tracing output.
+    std::thread::spawn(move || {
+        while let Ok(event) = event_rx.recv() {
+            tracing::info!(?event, "engine event");
+        }
+    });
 
     // imitation market data generator.
     std::thread::spawn(move || {
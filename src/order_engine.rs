@@ -1,11 +1,15 @@
-use std::{collections::hash_map::Entry, sync::Arc, time::Duration};
+use std::{sync::Arc, time::Duration};
 
 use parking_lot::Mutex;
 use rand::Rng;
 use tracing::info;
 
 use crate::{
-    portfolio::{Portfolio, Position},
+    allocator::{FixedUnitAllocator, OrderAllocator},
+    event::Event,
+    portfolio::{FillEvent, FillSide, FillUpdater, Portfolio},
+    storage::Store,
+    strategies::TradeSignal,
     MarketPair,
 };
 
@@ -16,9 +20,45 @@ pub enum OrderEvent {
     Close(MarketPair, Arc<Mutex<Portfolio>>),
 }
 
-pub struct OrderEngine;
+/// Runs the (blocking, synchronous-Postgres-client) `persist_fill` call
+/// on a blocking-pool thread instead of the order engine's single
+/// current-thread runtime worker, so a slow DB round-trip doesn't
+/// serialize every other fill behind it.
+async fn persist_fill_blocking(store: Arc<dyn Store + Send + Sync>, fill: FillEvent) {
+    let result = tokio::task::spawn_blocking(move || store.persist_fill(&fill)).await;
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(why)) => tracing::warn!("failed to persist fill: {why}"),
+        Err(join_err) => tracing::warn!("persist_fill task panicked: {join_err}"),
+    }
+}
+
+pub struct OrderEngine {
+    allocator: Arc<dyn OrderAllocator + Send + Sync>,
+    event_sender: flume::Sender<Event>,
+    store: Arc<dyn Store + Send + Sync>,
+}
 
 impl OrderEngine {
+    pub fn new(
+        allocator: Arc<dyn OrderAllocator + Send + Sync>,
+        event_sender: flume::Sender<Event>,
+        store: Arc<dyn Store + Send + Sync>,
+    ) -> OrderEngine {
+        OrderEngine {
+            allocator,
+            event_sender,
+            store,
+        }
+    }
+
+    pub fn with_default_allocator(
+        event_sender: flume::Sender<Event>,
+        store: Arc<dyn Store + Send + Sync>,
+    ) -> OrderEngine {
+        OrderEngine::new(Arc::new(FixedUnitAllocator), event_sender, store)
+    }
+
     pub fn start(self, order_rx: flume::Receiver<OrderEvent>) {
         tracing::trace!("Building order runtime");
         let rt = tokio::runtime::Builder::new_current_thread()
@@ -26,6 +66,10 @@ impl OrderEngine {
             .build()
             .expect("failed to build runtime");
 
+        let allocator = self.allocator;
+        let event_sender = self.event_sender;
+        let store = self.store;
+
         tracing::trace!("Starting order handle thread");
         let engine_handle = std::thread::spawn(move || {
             info!("Starting order handler");
@@ -36,46 +80,130 @@ impl OrderEngine {
                 while let Ok(event) = order_rx.recv_async().await {
                     match event {
                         OrderEvent::Close(pair, portfolio) => {
-                            tracing::trace!(pair = pair.asset, "Received close");
-                            let mut ptf = portfolio.lock();
-                            ptf.close_position(&pair);
-                            drop(ptf);
+                            info!(pair = pair.asset, "Spawning new CLOSE handler");
+                            let event_sender = event_sender.clone();
+                            let store = Arc::clone(&store);
+                            tokio::spawn(async move {
+                                // Simulate the broker fill interaction.
+                                tokio::time::sleep(Duration::from_millis(300)).await;
+
+                                // Confined to a plain (non-async) block so the
+                                // `ThreadRng` and `MutexGuard` it creates are
+                                // dropped before the `.await` below — neither
+                                // is `Send`, so they can't survive across it.
+                                let fill = {
+                                    let mut price_generator = rand::thread_rng();
+                                    let price = price_generator.gen_range(10.0..=20.0);
+
+                                    let mut ptf = portfolio.lock();
+                                    let quantity = ptf
+                                        .positions
+                                        .get(&pair)
+                                        .map(|position| position.size)
+                                        .unwrap_or(0);
+                                    let fill = FillEvent {
+                                        market_pair: pair.clone(),
+                                        side: FillSide::Close,
+                                        price,
+                                        quantity,
+                                    };
+                                    ptf.update_from_fill(&fill);
+                                    fill
+                                };
+
+                                persist_fill_blocking(store, fill).await;
+
+                                let _ = event_sender.send(Event::OrderFilled(pair.clone()));
+                                let _ = event_sender.send(Event::PositionClosed(pair));
+                            });
                         }
 
                         OrderEvent::Short(market_pair, portfolio) => {
                             info!(pair = market_pair.asset, "Spawning new SHORT handler");
                             // spawn a new task to handle selling and logic.
+                            let allocator = Arc::clone(&allocator);
+                            let event_sender = event_sender.clone();
+                            let store = Arc::clone(&store);
                             tokio::spawn(async move {
                                 // Simulate the broker fill interaction.
                                 tokio::time::sleep(Duration::from_millis(300)).await;
 
-                                let mut price_generator = rand::thread_rng();
-                                let price = price_generator.gen_range(10.0..=20.0);
+                                // Confined to a plain (non-async) block so the
+                                // `ThreadRng` and `MutexGuard` it creates are
+                                // dropped before the `.await` below — neither
+                                // is `Send`, so they can't survive across it.
+                                let (fill, position) = {
+                                    let mut price_generator = rand::thread_rng();
+                                    let price = price_generator.gen_range(10.0..=20.0);
+
+                                    // LOCK AFTER.
+                                    let mut ptf = portfolio.lock();
+                                    let quantity = {
+                                        let existing = ptf.positions.get(&market_pair);
+                                        allocator.allocate(ptf.cash, price, &TradeSignal::Short, existing)
+                                    };
+                                    let fill = FillEvent {
+                                        market_pair: market_pair.clone(),
+                                        side: FillSide::Short,
+                                        price,
+                                        quantity,
+                                    };
+                                    ptf.update_from_fill(&fill);
+                                    let position = ptf.positions.get(&market_pair).cloned();
+                                    (fill, position)
+                                };
+
+                                persist_fill_blocking(store, fill).await;
 
-                                // LOCK AFTER.
-                                let mut ptf = portfolio.lock();
-                                if let Entry::Vacant(e) = ptf.positions.entry(market_pair) {
-                                    e.insert(Position { price, size: -1 });
-                                    info!("SHORT position");
+                                info!("SHORT position");
+                                let _ = event_sender.send(Event::OrderFilled(market_pair.clone()));
+                                if let Some(position) = position {
+                                    let _ =
+                                        event_sender.send(Event::PositionOpened(market_pair, position));
                                 }
-                                drop(ptf);
                             });
                         }
 
                         OrderEvent::Long(market_pair, portfolio) => {
                             info!(pair = market_pair.asset, "Spawning new LONG handler");
+                            let allocator = Arc::clone(&allocator);
+                            let event_sender = event_sender.clone();
+                            let store = Arc::clone(&store);
                             tokio::spawn(async move {
                                 tokio::time::sleep(Duration::from_millis(300)).await;
 
-                                let mut price_generator = rand::thread_rng();
-                                let price = price_generator.gen_range(10.0..=20.0);
+                                // Confined to a plain (non-async) block so the
+                                // `ThreadRng` and `MutexGuard` it creates are
+                                // dropped before the `.await` below — neither
+                                // is `Send`, so they can't survive across it.
+                                let (fill, position) = {
+                                    let mut price_generator = rand::thread_rng();
+                                    let price = price_generator.gen_range(10.0..=20.0);
+
+                                    let mut ptf = portfolio.lock();
+                                    let quantity = {
+                                        let existing = ptf.positions.get(&market_pair);
+                                        allocator.allocate(ptf.cash, price, &TradeSignal::Long, existing)
+                                    };
+                                    let fill = FillEvent {
+                                        market_pair: market_pair.clone(),
+                                        side: FillSide::Long,
+                                        price,
+                                        quantity,
+                                    };
+                                    ptf.update_from_fill(&fill);
+                                    let position = ptf.positions.get(&market_pair).cloned();
+                                    (fill, position)
+                                };
+
+                                persist_fill_blocking(store, fill).await;
 
-                                let mut ptf = portfolio.lock();
-                                if let Entry::Vacant(e) = ptf.positions.entry(market_pair) {
-                                    e.insert(Position { price, size: 1 });
-                                    info!("Bought position");
+                                info!("Bought position");
+                                let _ = event_sender.send(Event::OrderFilled(market_pair.clone()));
+                                if let Some(position) = position {
+                                    let _ =
+                                        event_sender.send(Event::PositionOpened(market_pair, position));
                                 }
-                                drop(ptf);
                             });
                         }
                         OrderEvent::Reverse(mp) => {
@@ -1,28 +1,69 @@
 use std::collections::HashMap;
 
 use bon::Builder;
-use time::OffsetDateTime;
 use uuid::Uuid;
 
-use crate::{Command, MarketPair};
+use crate::{trader::Candle, MarketPair};
 
-enum DataKind {
-    Candle(String),
+/// Which side of the book a fill landed on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillSide {
+    Long,
+    Short,
+    Close,
 }
 
-struct MarketEvent {
-    time: OffsetDateTime,
-    kind: DataKind,
+/// A single fill reported by the order engine: which pair, which side,
+/// and the price/quantity the (simulated) broker executed at.
+#[derive(Debug, Clone)]
+pub struct FillEvent {
+    pub market_pair: MarketPair,
+    pub side: FillSide,
+    pub price: f32,
+    pub quantity: i32,
+}
+
+/// Updates a position's unrealized P&L from the latest market price.
+pub trait MarketUpdater {
+    fn update_from_market(&mut self, candle: &Candle);
 }
 
-trait MarketUpdater {
-    fn update_from_market(&mut self, market_event: &MarketEvent);
+/// Applies a fill to the portfolio: opens/records a position, or closes
+/// one and realizes its P&L.
+pub trait FillUpdater {
+    fn update_from_fill(&mut self, fill: &FillEvent);
 }
 
 #[derive(PartialEq, Debug, Clone)]
 pub struct Position {
     pub size: i32,
     pub price: f32,
+    pub unrealized_pnl: f32,
+    pub realized_pnl: f32,
+}
+
+impl Position {
+    pub fn new(price: f32, size: i32) -> Position {
+        Position {
+            size,
+            price,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+        }
+    }
+}
+
+impl MarketUpdater for Position {
+    fn update_from_market(&mut self, candle: &Candle) {
+        self.unrealized_pnl = (candle.close - self.price) * self.size as f32;
+    }
+}
+
+/// A single timestamped sample of total portfolio equity.
+#[derive(Debug, Clone, Copy)]
+pub struct EquityPoint {
+    pub timestamp: i64,
+    pub equity: f32,
 }
 
 #[derive(Builder)]
@@ -30,12 +71,181 @@ pub struct Portfolio {
     pub engine_id: Uuid,
     pub markets: Vec<MarketPair>,
     pub positions: HashMap<MarketPair, Position>,
+    /// realized P&L banked from closed positions.
+    #[builder(default)]
+    pub equity: f32,
+    #[builder(default)]
+    pub equity_curve: Vec<EquityPoint>,
+    /// cash available to draw against for new entries.
+    #[builder(default = 10_000.0)]
+    pub cash: f32,
 }
 
 impl Portfolio {
-    /// closes a position.
+    /// closes a position, realizing its P&L into the running equity.
     pub fn close_position(&mut self, market_pair: &MarketPair) {
         tracing::trace!("Closing position");
-        self.positions.remove(market_pair);
+        if let Some(position) = self.positions.remove(market_pair) {
+            self.equity += position.unrealized_pnl;
+        }
+    }
+
+    /// Total unrealized P&L across every open position.
+    pub fn unrealized_pnl(&self) -> f32 {
+        self.positions.values().map(|p| p.unrealized_pnl).sum()
+    }
+
+    /// Records the current total equity (realized + unrealized) as a new
+    /// point on the equity curve.
+    pub fn record_equity_point(&mut self, timestamp: i64) {
+        let equity = self.equity + self.unrealized_pnl();
+        self.equity_curve.push(EquityPoint { timestamp, equity });
+    }
+}
+
+impl FillUpdater for Portfolio {
+    fn update_from_fill(&mut self, fill: &FillEvent) {
+        match fill.side {
+            FillSide::Close => {
+                if let Some(mut position) = self.positions.remove(&fill.market_pair) {
+                    position.realized_pnl = (fill.price - position.price) * position.size as f32;
+                    self.equity += position.realized_pnl;
+                    self.cash += fill.price * position.size.unsigned_abs() as f32;
+                }
+            }
+            FillSide::Long | FillSide::Short => {
+                // A declined fill (the allocator couldn't afford even a
+                // single unit) carries `quantity: 0`. Recording it as a
+                // position would leave a zero-size entry blocking every
+                // later fill for this pair behind the `contains_key` check
+                // below, without ever having actually opened anything.
+                if fill.quantity == 0 {
+                    return;
+                }
+                if self.positions.contains_key(&fill.market_pair) {
+                    return;
+                }
+                self.cash -= fill.price * fill.quantity.unsigned_abs() as f32;
+                self.positions
+                    .insert(fill.market_pair.clone(), Position::new(fill.price, fill.quantity));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn portfolio() -> Portfolio {
+        Portfolio::builder()
+            .engine_id(Uuid::new_v4())
+            .markets(Vec::new())
+            .positions(HashMap::new())
+            .build()
+    }
+
+    #[test]
+    fn long_fill_debits_cash_and_opens_a_position() {
+        let mut ptf = portfolio();
+        let pair = MarketPair::new("SUI", "USD");
+        let starting_cash = ptf.cash;
+
+        ptf.update_from_fill(&FillEvent {
+            market_pair: pair.clone(),
+            side: FillSide::Long,
+            price: 10.0,
+            quantity: 5,
+        });
+
+        assert_eq!(ptf.cash, starting_cash - 50.0);
+        assert_eq!(ptf.positions.get(&pair).unwrap().size, 5);
+    }
+
+    #[test]
+    fn zero_quantity_fill_does_not_open_a_position() {
+        let mut ptf = portfolio();
+        let pair = MarketPair::new("SUI", "USD");
+        let starting_cash = ptf.cash;
+
+        ptf.update_from_fill(&FillEvent {
+            market_pair: pair.clone(),
+            side: FillSide::Long,
+            price: 10.0,
+            quantity: 0,
+        });
+
+        assert_eq!(ptf.cash, starting_cash);
+        assert!(!ptf.positions.contains_key(&pair));
+    }
+
+    #[test]
+    fn close_fill_realizes_pnl_and_credits_cash() {
+        let mut ptf = portfolio();
+        let pair = MarketPair::new("SUI", "USD");
+
+        ptf.update_from_fill(&FillEvent {
+            market_pair: pair.clone(),
+            side: FillSide::Long,
+            price: 10.0,
+            quantity: 5,
+        });
+
+        ptf.update_from_fill(&FillEvent {
+            market_pair: pair.clone(),
+            side: FillSide::Close,
+            price: 12.0,
+            quantity: 0,
+        });
+
+        assert!(!ptf.positions.contains_key(&pair));
+        assert_eq!(ptf.equity, 10.0); // (12 - 10) * 5
+        assert_eq!(ptf.cash, 10_000.0 - 50.0 + 60.0);
+    }
+
+    #[test]
+    fn unrealized_pnl_sums_every_open_position() {
+        let mut ptf = portfolio();
+        ptf.positions.insert(
+            MarketPair::new("SUI", "USD"),
+            Position {
+                size: 2,
+                price: 10.0,
+                unrealized_pnl: 5.0,
+                realized_pnl: 0.0,
+            },
+        );
+        ptf.positions.insert(
+            MarketPair::new("SOL", "USD"),
+            Position {
+                size: -1,
+                price: 20.0,
+                unrealized_pnl: -3.0,
+                realized_pnl: 0.0,
+            },
+        );
+
+        assert_eq!(ptf.unrealized_pnl(), 2.0);
+    }
+
+    #[test]
+    fn record_equity_point_captures_realized_plus_unrealized() {
+        let mut ptf = portfolio();
+        ptf.equity = 100.0;
+        ptf.positions.insert(
+            MarketPair::new("SUI", "USD"),
+            Position {
+                size: 1,
+                price: 10.0,
+                unrealized_pnl: 25.0,
+                realized_pnl: 0.0,
+            },
+        );
+
+        ptf.record_equity_point(1_700_000_000);
+
+        let point = ptf.equity_curve.last().unwrap();
+        assert_eq!(point.timestamp, 1_700_000_000);
+        assert_eq!(point.equity, 125.0);
     }
 }
@@ -0,0 +1,122 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::trader::Candle;
+
+/// A candle timeframe, expressed as a bucket width in seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Resolution {
+    M1,
+    M5,
+    M15,
+    H1,
+    H4,
+    D1,
+}
+
+const RESOLUTIONS: [Resolution; 6] = [
+    Resolution::M1,
+    Resolution::M5,
+    Resolution::M15,
+    Resolution::H1,
+    Resolution::H4,
+    Resolution::D1,
+];
+
+impl Resolution {
+    fn seconds(&self) -> i64 {
+        match self {
+            Resolution::M1 => 60,
+            Resolution::M5 => 5 * 60,
+            Resolution::M15 => 15 * 60,
+            Resolution::H1 => 60 * 60,
+            Resolution::H4 => 4 * 60 * 60,
+            Resolution::D1 => 24 * 60 * 60,
+        }
+    }
+
+    /// Floors `timestamp` to this resolution's bucket boundary.
+    fn floor(&self, timestamp: i64) -> i64 {
+        let width = self.seconds();
+        timestamp - timestamp.rem_euclid(width)
+    }
+}
+
+/// Folds an incoming base-candle stream into every tracked [`Resolution`]
+/// by bucketing on timestamp: first `open`, running `max(high)`/
+/// `min(low)`, last `close`, summed `volume`. Kept per-market alongside
+/// the raw candle stream so a strategy can evaluate, say, H1 RSI while
+/// ticks arrive every 100ms.
+#[derive(Debug, Clone)]
+pub struct CandleAggregator {
+    buckets: HashMap<Resolution, VecDeque<Candle>>,
+    /// Resolutions that have received at least one candle, so the very
+    /// first bucket opened for a resolution isn't reported as a closed
+    /// bar (there's no prior bar to have closed).
+    initialized: std::collections::HashSet<Resolution>,
+}
+
+impl CandleAggregator {
+    pub fn new() -> CandleAggregator {
+        let buckets = RESOLUTIONS
+            .into_iter()
+            .map(|res| (res, VecDeque::new()))
+            .collect();
+        CandleAggregator {
+            buckets,
+            initialized: std::collections::HashSet::new(),
+        }
+    }
+
+    /// Folds `candle` into every tracked resolution, returning the
+    /// resolutions whose bucket boundary was just crossed by this candle
+    /// (i.e. produced a freshly closed bar) so resolution-gated
+    /// strategies know when to evaluate. The first candle ever seen for
+    /// a resolution opens its bucket but is never reported as "closed".
+    pub fn push(&mut self, candle: &Candle) -> Vec<Resolution> {
+        let mut closed = Vec::new();
+
+        for res in RESOLUTIONS {
+            let bucket_ts = res.floor(candle.timestamp);
+            let bucket = self
+                .buckets
+                .get_mut(&res)
+                .expect("all resolutions are pre-populated in new()");
+
+            match bucket.front_mut() {
+                Some(current) if current.timestamp == bucket_ts => {
+                    current.high = current.high.max(candle.high);
+                    current.low = current.low.min(candle.low);
+                    current.close = candle.close;
+                    current.volume += candle.volume;
+                }
+                _ => {
+                    let was_initialized = !self.initialized.insert(res);
+                    bucket.push_front(Candle {
+                        open: candle.open,
+                        high: candle.high,
+                        low: candle.low,
+                        close: candle.close,
+                        volume: candle.volume,
+                        timestamp: bucket_ts,
+                    });
+                    if was_initialized {
+                        closed.push(res);
+                    }
+                }
+            }
+        }
+
+        closed
+    }
+
+    /// The bucketed candles for a given resolution, newest-first.
+    pub fn candles(&self, resolution: Resolution) -> &VecDeque<Candle> {
+        &self.buckets[&resolution]
+    }
+}
+
+impl Default for CandleAggregator {
+    fn default() -> CandleAggregator {
+        CandleAggregator::new()
+    }
+}
@@ -1,10 +1,11 @@
-use std::{sync::Arc, time::Duration};
+use std::{collections::VecDeque, sync::Arc, time::Duration};
 
 use parking_lot::Mutex;
 
 use crate::{
     portfolio::{Portfolio, Position},
-    trader::MarketData,
+    resolution::Resolution,
+    trader::{Candle, MarketData},
     MarketPair,
 };
 
@@ -19,6 +20,17 @@ pub struct SystemCtx {
     pub position: Position,
     pub market_pair: MarketPair,
     pub market_data: MarketData,
+    /// "Now" as of this tick: wall-clock time live, or the timestamp of
+    /// the most recently replayed candle in a backtest.
+    pub timestamp: i64,
+}
+
+impl SystemCtx {
+    /// The bucketed candles for a given resolution, newest-first, so a
+    /// strategy can evaluate on a higher timeframe than the raw feed.
+    pub fn candles(&self, resolution: Resolution) -> &VecDeque<Candle> {
+        self.market_data.aggregator.candles(resolution)
+    }
 }
 
 pub trait SignalGenerator {
@@ -46,35 +58,181 @@ impl SignalGenerator for SimpleStrat {
     }
 }
 
+/// RSI strategy using Wilder's smoothing over `period` bars of `resolution`.
 pub struct Rsi {
     pub period: usize,
+    /// RSI below this is oversold -> go Long.
+    pub oversold: f32,
+    /// RSI above this is overbought -> Short (flat) or Close (in position).
+    pub overbought: f32,
+    /// Candle timeframe the RSI math runs over, e.g. `Resolution::H1` to
+    /// trade an hourly RSI off a feed that ticks every minute.
+    pub resolution: Resolution,
 }
 
 impl SignalGenerator for Rsi {
     fn generate_signal(&mut self, ctx: SystemCtx) -> Option<TradeSignal> {
-        let candles = &ctx.market_data.candles;
-        if candles.is_empty() {
-            tracing::warn!("Empty candles");
+        if self.period == 0 {
+            tracing::warn!("RSI period must be at least 1");
             return None;
         }
 
-        match ctx.position.size {
-            _ => {
-                tracing::trace!("Has position");
-                let Some(first_candle) = ctx.market_data.candles.front() else {
-                    return None;
-                };
-
-                if ctx.position.price > first_candle.close {
-                    return Some(TradeSignal::Close);
-                }
-            }
-            0 => {
-                tracing::info!("Sending Short signal");
-                return Some(TradeSignal::Short);
-            }
+        let candles = ctx.candles(self.resolution);
+        // candles are newest-first (pushed via push_front), so walk them
+        // in chronological order for the delta/average calculations.
+        if candles.len() < self.period + 1 {
+            tracing::trace!("Not enough candles for RSI({})", self.period);
+            return None;
+        }
+
+        let closes: Vec<f32> = candles.iter().rev().map(|c| c.close).collect();
+        let mut gains = Vec::with_capacity(closes.len() - 1);
+        let mut losses = Vec::with_capacity(closes.len() - 1);
+        for pair in closes.windows(2) {
+            let delta = pair[1] - pair[0];
+            gains.push(delta.max(0.0));
+            losses.push((-delta).max(0.0));
+        }
+
+        // seed with the simple mean of the first `period` gains/losses,
+        // then apply Wilder's smoothing for every bar after that.
+        let mut avg_gain = gains[..self.period].iter().sum::<f32>() / self.period as f32;
+        let mut avg_loss = losses[..self.period].iter().sum::<f32>() / self.period as f32;
+
+        for i in self.period..gains.len() {
+            avg_gain = (avg_gain * (self.period - 1) as f32 + gains[i]) / self.period as f32;
+            avg_loss = (avg_loss * (self.period - 1) as f32 + losses[i]) / self.period as f32;
+        }
+
+        let rsi = if avg_loss == 0.0 {
+            100.0
+        } else {
+            let rs = avg_gain / avg_loss;
+            100.0 - 100.0 / (1.0 + rs)
+        };
+        tracing::trace!(rsi, "computed RSI");
+
+        if rsi < self.oversold {
+            tracing::info!(rsi, "Sending Long signal");
+            return Some(TradeSignal::Long);
+        }
+
+        if rsi > self.overbought {
+            return Some(if ctx.position.size == 0 {
+                tracing::info!(rsi, "Sending Short signal");
+                TradeSignal::Short
+            } else {
+                tracing::info!(rsi, "Sending Close signal");
+                TradeSignal::Close
+            });
         }
 
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle_at(close: f32, timestamp: i64) -> Candle {
+        Candle {
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 0,
+            timestamp,
+        }
+    }
+
+    /// Builds a context whose M1 aggregator bucket contains one bar per
+    /// close, so `Rsi` reading `ctx.candles(Resolution::M1)` sees exactly
+    /// `closes`. `closes` is given chronologically; each is 60s apart so
+    /// every push opens a fresh M1 bucket instead of folding into the last.
+    fn ctx_with_closes(closes: &[f32], position_size: i32) -> SystemCtx {
+        let mut market_data = MarketData::new();
+        for (i, &close) in closes.iter().enumerate() {
+            market_data.aggregator.push(&candle_at(close, i as i64 * 60));
+        }
+
+        SystemCtx {
+            position: Position::new(0.0, position_size),
+            market_pair: MarketPair::new("SUI", "USD"),
+            market_data,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn rsi_zero_period_never_signals() {
+        let mut rsi = Rsi {
+            period: 0,
+            oversold: 30.0,
+            overbought: 70.0,
+            resolution: Resolution::M1,
+        };
+
+        let ctx = ctx_with_closes(&[10.0, 9.0, 8.0], 0);
+        assert!(rsi.generate_signal(ctx).is_none());
+    }
+
+    #[test]
+    fn rsi_not_enough_candles_is_none() {
+        let mut rsi = Rsi {
+            period: 14,
+            oversold: 30.0,
+            overbought: 70.0,
+            resolution: Resolution::M1,
+        };
+
+        let ctx = ctx_with_closes(&[10.0, 9.0, 8.0], 0);
+        assert!(rsi.generate_signal(ctx).is_none());
+    }
+
+    #[test]
+    fn rsi_straight_downtrend_is_fully_oversold() {
+        let mut rsi = Rsi {
+            period: 2,
+            oversold: 30.0,
+            overbought: 70.0,
+            resolution: Resolution::M1,
+        };
+
+        // Every bar loses, never gains, so Wilder's RSI bottoms out at 0
+        // and a flat trader should get a Long signal.
+        let ctx = ctx_with_closes(&[10.0, 9.0, 8.0, 7.0], 0);
+        assert!(matches!(rsi.generate_signal(ctx), Some(TradeSignal::Long)));
+    }
+
+    #[test]
+    fn rsi_straight_uptrend_closes_an_open_position() {
+        let mut rsi = Rsi {
+            period: 2,
+            oversold: 30.0,
+            overbought: 70.0,
+            resolution: Resolution::M1,
+        };
+
+        // Every bar gains, never loses, so RSI tops out at 100; with an
+        // open position that should be a Close, not a fresh Short.
+        let ctx = ctx_with_closes(&[7.0, 8.0, 9.0, 10.0], 1);
+        assert!(matches!(rsi.generate_signal(ctx), Some(TradeSignal::Close)));
+    }
+
+    #[test]
+    fn rsi_reads_the_configured_resolution_not_the_raw_feed() {
+        // H1 bucket never closes a second bar from these M1-spaced
+        // candles, so an Rsi configured for H1 sees too few bars and
+        // stays flat even though the M1 stream alone would satisfy it.
+        let mut rsi = Rsi {
+            period: 2,
+            oversold: 30.0,
+            overbought: 70.0,
+            resolution: Resolution::H1,
+        };
+
+        let ctx = ctx_with_closes(&[10.0, 9.0, 8.0, 7.0], 0);
+        assert!(rsi.generate_signal(ctx).is_none());
+    }
+}
@@ -1,20 +1,22 @@
 use std::{
     collections::{HashMap, VecDeque},
     sync::Arc,
-    thread::sleep,
     time::Duration,
 };
 
 use bon::Builder;
-use crossbeam::channel::tick;
 use parking_lot::Mutex;
-use tracing::{instrument, warn};
+use tracing::instrument;
 use uuid::Uuid;
 
 use crate::{
+    event::Event,
+    feed::{Clock, FeedEvent, FeedOutcome, MarketFeed},
     indicators::Ohlc,
     order_engine::OrderEvent,
-    portfolio::{Portfolio, Position},
+    portfolio::{MarketUpdater, Portfolio, Position},
+    resolution::{CandleAggregator, Resolution},
+    storage::Store,
     strategies::{SignalGenerator, SystemCtx, TradeSignal},
     Command, MarketEvent, MarketPair,
 };
@@ -32,12 +34,20 @@ pub struct Candle {
 #[derive(Debug, Clone)]
 pub struct MarketData {
     pub candles: VecDeque<Candle>,
+    pub aggregator: CandleAggregator,
+    /// Resolutions whose bucket was closed by the most recently pushed
+    /// candle, i.e. since the last time this was overwritten. Lets a
+    /// tick gate strategy evaluation on a completed bar for the
+    /// resolution it trades, instead of evaluating on every tick.
+    pub last_closed: Vec<Resolution>,
 }
 
 impl MarketData {
     pub fn new() -> MarketData {
         MarketData {
             candles: VecDeque::new(),
+            aggregator: CandleAggregator::new(),
+            last_closed: Vec::new(),
         }
     }
 }
@@ -53,6 +63,11 @@ pub struct Trader {
     pub portfolio: Arc<Mutex<Portfolio>>,
     pub order_sender: flume::Sender<OrderEvent>,
     pub command_recv: flume::Receiver<Command>,
+    pub event_sender: flume::Sender<Event>,
+    pub store: Arc<dyn Store + Send + Sync>,
+    // resolution the strategy trades on; ticks are only evaluated once a
+    // bar at this resolution has just closed.
+    pub resolution: Resolution,
 
     // interval the trader will run over.
     tick_rate: Duration,
@@ -60,86 +75,170 @@ pub struct Trader {
     strategy: Box<dyn SignalGenerator + Send>,
 }
 
+/// Everything a [`Trader`] needs to run a loop: where its market data and
+/// ticks come from, and what "now" means.
 pub struct TraderConfig {
     pub exit_recv: flume::Receiver<bool>,
-    pub market_event_recv: flume::Receiver<MarketEvent>,
+    pub feed: Box<dyn MarketFeed + Send>,
+    pub clock: Box<dyn Clock + Send>,
 }
 
 impl Trader {
     /// starts the traders event loop.
+    ///
+    /// Blocks on a select across the exit channel, command channel,
+    /// market-event channel and tick channel simultaneously (see
+    /// [`MarketFeed`]), so the thread only wakes when there's real work
+    /// instead of polling every few milliseconds.
     #[instrument(skip(self, config), fields(ticker = self.market_pair.asset))]
     pub fn start(mut self, config: TraderConfig) {
         tracing::info!("Starting trader");
 
         let TraderConfig {
             exit_recv,
-            market_event_recv,
+            mut feed,
+            mut clock,
         } = config;
-        let ticker = tick(self.tick_rate);
 
-        // NON TERMINATING
+        // NON TERMINATING (except in backtest mode, where it ends once
+        // the feed is exhausted).
         'strategy_loop: loop {
-            if let Ok(should_stop) = exit_recv.try_recv() {
-                if should_stop {
+            match feed.next(&exit_recv, &self.command_recv) {
+                FeedOutcome::Exit => {
                     tracing::warn!("Fully stopping trader");
                     break;
                 }
-            }
-            if let Ok(Command::ForceExit) = self.command_recv.try_recv() {
-                tracing::warn!("STOPPING");
-                break;
-            }
+                FeedOutcome::Command(Command::ForceExit) => {
+                    tracing::warn!("STOPPING");
+                    break;
+                }
+                FeedOutcome::Command(_other) => {
+                    // `self.command_recv` is this trader's own dedicated
+                    // channel (fanned out to by the CommandEngine), so
+                    // `ForceExit` is the only variant it ever forwards.
+                    // CloseAllPositions / AddPortfolioPosition /
+                    // PortfolioStatus are handled centrally instead.
+                }
+                FeedOutcome::Feed(FeedEvent::Candle(ohlc)) => {
+                    clock.advance_to(ohlc.timestamp);
 
-            if let Ok(MarketEvent::Ohlc(ohlc)) = market_event_recv.try_recv() {
-                self.market_data.candles.push_front(ohlc);
-            }
+                    let mut ptf = self.portfolio.lock();
+                    if let Some(position) = ptf.positions.get_mut(&self.market_pair) {
+                        position.update_from_market(&ohlc);
+                    }
+                    ptf.record_equity_point(clock.now());
+                    drop(ptf);
 
-            if ticker.try_recv().is_ok() {
-                let ptf = self.portfolio.lock();
+                    let closed = self.market_data.aggregator.push(&ohlc);
+                    if !closed.is_empty() {
+                        tracing::trace!(?closed, "candle bucket closed");
+                    }
+                    self.market_data.last_closed = closed;
 
-                let Some(position) = ptf.positions.get(&self.market_pair) else {
-                    warn!("No position found for {:?}", self.market_pair);
-                    continue;
-                };
+                    let _ = self
+                        .store
+                        .persist_candles(&self.market_pair, Resolution::M1, &[ohlc.clone()])
+                        .inspect_err(|why| tracing::warn!("failed to persist candle: {why}"));
 
-                let ctx = SystemCtx {
-                    position: position.clone(),
-                    market_pair: self.market_pair.clone(),
-                    market_data: self.market_data.clone(),
-                };
+                    self.market_data.candles.push_front(ohlc);
+                }
+                FeedOutcome::Feed(FeedEvent::Tick) => {
+                    if !self.market_data.last_closed.contains(&self.resolution) {
+                        tracing::trace!(resolution = ?self.resolution, "bar not yet closed, skipping tick");
+                        continue;
+                    }
 
-                drop(ptf);
+                    let ptf = self.portfolio.lock();
 
-                if let Some(signal) = self.strategy.generate_signal(ctx) {
-                    tracing::trace!("matching signal to order event");
-                    let event = match signal {
-                        TradeSignal::Close => {
-                            OrderEvent::Close(self.market_pair.clone(), self.portfolio.clone())
-                        }
+                    // Absence from `positions` means flat, not "not ready
+                    // yet" — nothing seeds a starting position for a
+                    // market, live or in a backtest, so treating it as a
+                    // skip-forever condition meant strategies never ran.
+                    let position = ptf
+                        .positions
+                        .get(&self.market_pair)
+                        .cloned()
+                        .unwrap_or_else(|| Position::new(0.0, 0));
+
+                    let ctx = SystemCtx {
+                        position,
+                        market_pair: self.market_pair.clone(),
+                        market_data: self.market_data.clone(),
+                        timestamp: clock.now(),
+                    };
 
-                        TradeSignal::Long => {
-                            OrderEvent::Long(self.market_pair.clone(), self.portfolio.clone())
-                        }
+                    drop(ptf);
 
-                        TradeSignal::Short => {
-                            OrderEvent::Short(self.market_pair.clone(), self.portfolio.clone())
-                        }
+                    if let Some(signal) = self.strategy.generate_signal(ctx) {
+                        tracing::trace!("matching signal to order event");
+                        let _ = self
+                            .event_sender
+                            .send(Event::SignalGenerated(self.market_pair.clone()));
+                        let event = match signal {
+                            TradeSignal::Close => {
+                                OrderEvent::Close(self.market_pair.clone(), self.portfolio.clone())
+                            }
+
+                            TradeSignal::Long => {
+                                OrderEvent::Long(self.market_pair.clone(), self.portfolio.clone())
+                            }
+
+                            TradeSignal::Short => {
+                                OrderEvent::Short(self.market_pair.clone(), self.portfolio.clone())
+                            }
+                        };
+                        let _ = self
+                            .order_sender
+                            .send(event)
+                            .inspect_err(|why| tracing::error!("{why}"));
                     };
-                    let _ = self
-                        .order_sender
-                        .send(event)
-                        .inspect_err(|why| tracing::error!("{why}"));
-                };
+                }
+                FeedOutcome::Idle => {
+                    if feed.is_exhausted() {
+                        tracing::info!("Feed exhausted, stopping trader");
+                        break;
+                    }
+                }
             }
-            sleep(Duration::from_millis(10));
         }
     }
 }
 
 /// handle to send market data to.
+///
+/// Also holds the engine's `stop` sender: `Trader::start`'s exit channel is
+/// only "armed" while at least one sender is alive, so this must be kept
+/// around for as long as the traders should keep running. Dropping it (or
+/// never storing it) closes `exit_recv` immediately, which `Selector::wait`
+/// then reports as permanently ready — busy-spinning the trader loop
+/// instead of blocking.
 #[derive(Clone)]
 pub struct TradingEngineHandle {
     pub traders: HashMap<String, flume::Sender<MarketEvent>>,
+    pub stop: flume::Sender<bool>,
+}
+
+/// Which source of data and timing the engine's traders should run
+/// against. `Live` wires each trader to the real-time market-event
+/// channel; `Backtest` replays a fixed history per market so the same
+/// pipeline can be validated offline before going live.
+pub enum EngineMode {
+    Live,
+    Backtest {
+        candles: HashMap<MarketPair, Vec<Candle>>,
+    },
+}
+
+/// Result of running the engine: a handle to feed live market data, or
+/// a summary produced once every backtest feed has drained.
+pub enum TradingEngineOutcome {
+    Live(TradingEngineHandle),
+    Backtest(BacktestSummary),
+}
+
+#[derive(Debug, Default)]
+pub struct BacktestSummary {
+    pub traders_run: usize,
 }
 
 #[derive(Builder)]
@@ -153,33 +252,142 @@ pub struct TradingEngine {
 }
 
 impl TradingEngine {
-    pub fn start(&mut self) -> anyhow::Result<TradingEngineHandle> {
+    pub fn start(&mut self, mode: EngineMode) -> anyhow::Result<TradingEngineOutcome> {
         let traders = std::mem::take(&mut self.traders);
-
-        // handles to send market data.
-        let mut traders_map = HashMap::with_capacity(traders.len());
         let (stop_tx, stop_rx) = flume::bounded::<bool>(1);
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(traders.len().max(1))
+            .build()?;
+
+        match mode {
+            EngineMode::Live => {
+                let mut traders_map = HashMap::with_capacity(traders.len());
+
+                for trader in traders {
+                    let exit_recv = stop_rx.clone();
+                    let asset = trader.market_pair.asset.clone();
+                    let (market_event_send, market_event_recv) =
+                        flume::bounded::<MarketEvent>(512);
+                    let feed = Box::new(crate::feed::LiveFeed::new(
+                        market_event_recv,
+                        trader.tick_rate,
+                    ));
+
+                    thread_pool.spawn(move || {
+                        trader.start(TraderConfig {
+                            exit_recv,
+                            feed,
+                            clock: Box::new(crate::feed::WallClock),
+                        });
+                    });
+
+                    tracing::trace!("Inserting handles into stores");
+                    traders_map.insert(asset, market_event_send);
+                }
+
+                Ok(TradingEngineOutcome::Live(TradingEngineHandle {
+                    traders: traders_map,
+                    stop: stop_tx,
+                }))
+            }
 
-        let thread_pool = rayon::ThreadPoolBuilder::new().num_threads(2).build()?;
+            EngineMode::Backtest { mut candles } => {
+                let traders_run = traders.len();
 
-        for trader in traders {
-            let exit_recv = stop_rx.clone();
-            let asset = trader.market_pair.asset.clone();
-            let (market_event_send, market_event_recv) = flume::bounded::<MarketEvent>(512);
+                thread_pool.scope(|scope| {
+                    for trader in traders {
+                        let exit_recv = stop_rx.clone();
+                        let history = candles.remove(&trader.market_pair).unwrap_or_default();
+                        let feed = Box::new(crate::feed::HistoricalFeed::new(history));
 
-            thread_pool.spawn(move || {
-                trader.start(TraderConfig {
-                    exit_recv,
-                    market_event_recv,
+                        scope.spawn(move |_| {
+                            trader.start(TraderConfig {
+                                exit_recv,
+                                feed,
+                                clock: Box::new(crate::feed::BacktestClock::default()),
+                            });
+                        });
+                    }
                 });
-            });
 
-            tracing::trace!("Inserting handles into stores");
-            traders_map.insert(asset, market_event_send);
+                Ok(TradingEngineOutcome::Backtest(BacktestSummary {
+                    traders_run,
+                }))
+            }
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        feed::{BacktestClock, HistoricalFeed},
+        storage::NullStore,
+        strategies::SimpleStrat,
+    };
+
+    #[test]
+    fn backtest_evaluates_ticks_without_a_preexisting_position() {
+        // Nothing ever seeds a starting `Position` for a market, so the
+        // Tick arm must treat "absent from `positions`" as flat rather
+        // than stalling forever waiting for one to show up.
+        let pair = MarketPair::new("SUI", "USD");
+        let portfolio = Arc::new(Mutex::new(
+            Portfolio::builder()
+                .engine_id(Uuid::new_v4())
+                .markets(vec![pair.clone()])
+                .positions(HashMap::new())
+                .build(),
+        ));
+        let (order_tx, order_rx) = flume::bounded(8);
+        let (event_tx, _event_rx) = flume::bounded(8);
+        let (_cmd_tx, cmd_rx) = flume::bounded(8);
+        let (_exit_tx, exit_rx) = flume::bounded(1);
+
+        let trader = Trader::builder()
+            .engine_id(Uuid::new_v4())
+            .market_pair(pair)
+            .market_data(MarketData::new())
+            .command_recv(cmd_rx)
+            .portfolio(Arc::clone(&portfolio))
+            .order_sender(order_tx)
+            .event_sender(event_tx)
+            .store(Arc::new(NullStore))
+            .resolution(Resolution::M1)
+            .strategy(Box::new(SimpleStrat {}))
+            .tick_rate(Duration::from_secs(1))
+            .build();
+
+        // Two M1-resolution bars (60s apart) so the second candle closes
+        // the first bucket, arming the resolution gate for the tick after it.
+        let candles = vec![
+            Candle {
+                open: 10.0,
+                high: 10.0,
+                low: 10.0,
+                close: 10.0,
+                volume: 0,
+                timestamp: 0,
+            },
+            Candle {
+                open: 10.0,
+                high: 10.0,
+                low: 10.0,
+                close: 10.0,
+                volume: 0,
+                timestamp: 60,
+            },
+        ];
+
+        trader.start(TraderConfig {
+            exit_recv: exit_rx,
+            feed: Box::new(HistoricalFeed::new(candles)),
+            clock: Box::new(BacktestClock::default()),
+        });
 
-        Ok(TradingEngineHandle {
-            traders: traders_map,
-        })
+        assert!(matches!(order_rx.try_recv(), Ok(OrderEvent::Long(_, _))));
     }
 }